@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// How long a resolved favicon is trusted before we re-fetch it over the
+/// network. Callers can override this (see `fetch_og_metadata`'s
+/// `favicon_ttl_secs` parameter); this is only the fallback default.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+
+/// How long a single favicon confirmation request is allowed to hang
+/// before we give up on that candidate and try the next one.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedFaviconMeta {
+    content_type: String,
+    cached_at: u64,
+}
+
+struct IconCandidate {
+    href: String,
+    rank: u8,
+    size: u32,
+}
+
+/// `sizes="32x32"` → 1024, `sizes="48x48 32x32"` → the larger of the two,
+/// `sizes="any"` → treated as the best possible match. Dimensions come
+/// straight from a fetched page's HTML, so the multiply is saturating
+/// rather than a raw `*` that could overflow on something like
+/// `sizes="99999x99999"`.
+fn size_score(sizes: Option<&str>) -> u32 {
+    match sizes {
+        None => 0,
+        Some("any") => u32::MAX,
+        Some(sizes) => sizes
+            .split_whitespace()
+            .filter_map(|dim| {
+                let (w, h) = dim.split_once(['x', 'X'])?;
+                Some(w.parse::<u32>().ok()?.saturating_mul(h.parse::<u32>().ok()?))
+            })
+            .max()
+            .unwrap_or(0),
+    }
+}
+
+fn rel_rank(rel: &str) -> u8 {
+    match rel {
+        "icon" | "shortcut icon" => 2,
+        "apple-touch-icon" | "apple-touch-icon-precomposed" => 1,
+        _ => 0,
+    }
+}
+
+/// Builds the ranked candidate list: explicit `rel="icon"` links (largest
+/// declared `sizes` first), then apple-touch icons, then the root
+/// `/favicon.ico` fallback last.
+fn rank_candidates(links: &[HashMap<String, String>], resolve: impl Fn(&str) -> String, base_url: &str) -> Vec<IconCandidate> {
+    let mut candidates: Vec<IconCandidate> = links
+        .iter()
+        .filter_map(|link| {
+            // `lower_attrs` in `og.rs` only lowercases attribute *keys*, so a
+            // page with `rel="ICON"` or `rel="Shortcut Icon"` still needs
+            // folding here before it's compared against our rank table.
+            let rel = link.get("rel")?.to_ascii_lowercase();
+            let rank = rel_rank(&rel);
+            if rank == 0 {
+                return None;
+            }
+            let href = link.get("href")?;
+            Some(IconCandidate {
+                href: resolve(href),
+                rank,
+                size: size_score(link.get("sizes").map(String::as_str)),
+            })
+        })
+        .collect();
+
+    candidates.push(IconCandidate {
+        href: format!("{}/favicon.ico", base_url.trim_end_matches('/')),
+        rank: 0,
+        size: 0,
+    });
+
+    candidates.sort_by(|a, b| b.rank.cmp(&a.rank).then(b.size.cmp(&a.size)));
+    candidates
+}
+
+fn cache_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join("favicon_cache"))
+}
+
+fn safe_host(host: &str) -> String {
+    host.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' }).collect()
+}
+
+fn meta_path(app: &AppHandle, host: &str) -> Option<PathBuf> {
+    cache_dir(app).map(|dir| dir.join(format!("{}.json", safe_host(host))))
+}
+
+fn bytes_path(app: &AppHandle, host: &str) -> Option<PathBuf> {
+    cache_dir(app).map(|dir| dir.join(format!("{}.img", safe_host(host))))
+}
+
+/// Per-host locks so concurrent batch fetches (`fetch_og_metadata_batch`
+/// runs up to `BATCH_CONCURRENCY` requests at once) can't race each other
+/// into writing two different candidates' bytes/meta for the same host.
+fn host_locks() -> &'static StdMutex<HashMap<String, Arc<AsyncMutex<()>>>> {
+    static LOCKS: OnceLock<StdMutex<HashMap<String, Arc<AsyncMutex<()>>>>> = OnceLock::new();
+    LOCKS.get_or_init(|| StdMutex::new(HashMap::new()))
+}
+
+fn lock_for_host(host: &str) -> Arc<AsyncMutex<()>> {
+    let mut locks = host_locks().lock().unwrap_or_else(|e| e.into_inner());
+    locks.entry(host.to_string()).or_insert_with(|| Arc::new(AsyncMutex::new(()))).clone()
+}
+
+fn to_data_uri(content_type: &str, bytes: &[u8]) -> String {
+    format!("data:{};base64,{}", content_type, STANDARD.encode(bytes))
+}
+
+/// Reads the cached icon bytes for `host` if they exist and are still
+/// within `ttl`, so a fresh lookup never has to touch the network.
+fn read_cache(app: &AppHandle, host: &str, ttl: Duration) -> Option<String> {
+    let meta_path = meta_path(app, host)?;
+    let bytes_path = bytes_path(app, host)?;
+
+    let meta: CachedFaviconMeta = serde_json::from_slice(&std::fs::read(meta_path).ok()?).ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    if now.saturating_sub(meta.cached_at) > ttl.as_secs() {
+        return None;
+    }
+
+    let bytes = std::fs::read(bytes_path).ok()?;
+    Some(to_data_uri(&meta.content_type, &bytes))
+}
+
+/// Writes `contents` to `path` via a sibling temp file + rename, so a
+/// reader racing `read_cache` against this write (e.g. before a caller has
+/// acquired the per-host lock) only ever observes a complete old file or a
+/// complete new one — never a truncated in-progress write.
+fn atomic_write(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp_path = path.with_extension(format!(
+        "{}.tmp",
+        path.extension().and_then(|e| e.to_str()).unwrap_or("")
+    ));
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, path)
+}
+
+fn write_cache(app: &AppHandle, host: &str, content_type: &str, bytes: &[u8]) {
+    let (Some(dir), Some(meta_path), Some(bytes_path)) = (cache_dir(app), meta_path(app, host), bytes_path(app, host)) else {
+        return;
+    };
+    let Ok(now) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+        return;
+    };
+    let meta = CachedFaviconMeta {
+        content_type: content_type.to_string(),
+        cached_at: now.as_secs(),
+    };
+    if std::fs::create_dir_all(&dir).is_ok() {
+        if let Ok(json) = serde_json::to_vec(&meta) {
+            let _ = atomic_write(&meta_path, &json);
+            let _ = atomic_write(&bytes_path, bytes);
+        }
+    }
+}
+
+/// Confirms a candidate favicon is actually a reachable image and, if so,
+/// returns its bytes and content type for caching.
+async fn confirm(client: &reqwest::Client, href: &str) -> Option<(String, Vec<u8>)> {
+    let response = client.get(href).timeout(FETCH_TIMEOUT).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    if !content_type.starts_with("image/") {
+        return None;
+    }
+    let bytes = response.bytes().await.ok()?;
+    Some((content_type, bytes.to_vec()))
+}
+
+/// Picks the best favicon for `links`, confirming it's a real image before
+/// returning it. The chosen icon's bytes are cached to disk per-host for
+/// `ttl`, so repeat lookups for the same domain never touch the network
+/// at all, even across app restarts.
+pub async fn resolve(
+    client: &reqwest::Client,
+    app: &AppHandle,
+    host: &str,
+    links: &[HashMap<String, String>],
+    resolve_href: impl Fn(&str) -> String,
+    base_url: &str,
+    ttl: Duration,
+) -> Option<String> {
+    if let Some(cached) = read_cache(app, host, ttl) {
+        return Some(cached);
+    }
+
+    // Serialize cache misses per-host: a batch fetch can have several
+    // tasks resolve the same host concurrently, and without this lock
+    // they'd each independently `confirm` a candidate and race each
+    // other's `write_cache`, potentially leaving meta/bytes describing
+    // two different icons.
+    let lock = lock_for_host(host);
+    let _guard = lock.lock().await;
+
+    // Re-check now that we hold the lock: another task may have already
+    // populated the cache while we were waiting for it.
+    if let Some(cached) = read_cache(app, host, ttl) {
+        return Some(cached);
+    }
+
+    for candidate in rank_candidates(links, resolve_href, base_url) {
+        if let Some((content_type, bytes)) = confirm(client, &candidate.href).await {
+            write_cache(app, host, &content_type, &bytes);
+            return Some(to_data_uri(&content_type, &bytes));
+        }
+    }
+    None
+}
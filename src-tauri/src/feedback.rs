@@ -0,0 +1,409 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::HttpClient;
+
+const GITHUB_TOKEN: &str = env!("TALLY_GITHUB_TOKEN");
+const GITHUB_REPO: &str = "Querbox/tally-app";
+
+/// Every GitHub API call goes through the shared, otherwise-unbounded
+/// pooled client (see `http.rs`), so each request sets its own timeout
+/// rather than hanging forever on a stalled API or network.
+const GITHUB_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait before retrying a queued report: `30s * 2^attempts`,
+/// capped so a long-offline app doesn't wait days between tries.
+const BACKOFF_BASE_SECS: u64 = 30;
+const MAX_BACKOFF_SECS: u64 = 60 * 60 * 6;
+
+/// Default token-overlap ratio at or above which an existing issue is considered
+/// a match for a new report, used when the caller doesn't pass one. Applied
+/// after stopwords are stripped, so this is a ratio of *meaningful* words.
+const DEFAULT_DEDUPE_THRESHOLD: f64 = 0.75;
+
+/// Words too generic to count towards a title match — shared function
+/// words (or, for this app, the product name) would otherwise let
+/// unrelated reports cross the similarity threshold on overlap alone.
+const TITLE_STOPWORDS: &[&str] = &[
+    "a", "an", "the", "is", "are", "was", "were", "be", "been", "being", "on", "in", "at", "to",
+    "for", "of", "and", "or", "with", "when", "while", "after", "before", "this", "that", "it",
+    "app", "tally", "please", "bug", "issue",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GitHubIssueRequest {
+    title: String,
+    body: String,
+    labels: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GitHubIssueResponse {
+    number: u64,
+    html_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedbackResult {
+    success: bool,
+    issue_number: Option<u64>,
+    issue_url: Option<String>,
+    queued: bool,
+    /// A near-duplicate issue was filed as a comment instead of a new issue
+    /// (only set once the frontend has confirmed the match).
+    deduplicated: bool,
+    /// A near-duplicate issue was found and the frontend should ask the
+    /// user whether to comment on it instead — this is *not* an error, so
+    /// `error` stays `None` here and the frontend must check this flag
+    /// rather than inferring anything from `success: false` alone.
+    needs_confirmation: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchIssuesResponse {
+    items: Vec<SearchIssueItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchIssueItem {
+    number: u64,
+    html_url: String,
+    title: String,
+}
+
+/// A feedback report that failed to reach GitHub and is waiting for the
+/// next automatic retry.
+#[derive(Debug, Serialize, Deserialize)]
+struct QueuedFeedback {
+    issue: GitHubIssueRequest,
+    attempts: u32,
+    next_retry_at: u64,
+}
+
+fn labels_for(feedback_type: &str) -> Vec<String> {
+    match feedback_type {
+        "feature" => vec!["enhancement".to_string(), "from-app".to_string()],
+        "bug" => vec!["bug".to_string(), "from-app".to_string()],
+        "feedback" => vec!["feedback".to_string(), "from-app".to_string()],
+        _ => vec!["from-app".to_string()],
+    }
+}
+
+fn queue_path(app: &AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join("feedback_queue.json"))
+}
+
+fn read_queue(app: &AppHandle) -> Vec<QueuedFeedback> {
+    let Some(path) = queue_path(app) else {
+        return Vec::new();
+    };
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn write_queue(app: &AppHandle, queue: &[QueuedFeedback]) {
+    let Some(path) = queue_path(app) else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        if std::fs::create_dir_all(dir).is_err() {
+            return;
+        }
+    }
+    if let Ok(json) = serde_json::to_vec(queue) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn backoff_secs(attempts: u32) -> u64 {
+    (BACKOFF_BASE_SECS.saturating_mul(1 << attempts.min(10))).min(MAX_BACKOFF_SECS)
+}
+
+async fn post_issue(client: &reqwest::Client, issue: &GitHubIssueRequest) -> Result<GitHubIssueResponse, String> {
+    let response = client
+        .post(format!("https://api.github.com/repos/{}/issues", GITHUB_REPO))
+        .timeout(GITHUB_REQUEST_TIMEOUT)
+        .header("Authorization", format!("Bearer {}", GITHUB_TOKEN))
+        .header("User-Agent", "Tally-App")
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28")
+        .json(issue)
+        .send()
+        .await
+        .map_err(|e| format!("Netzwerkfehler: {}", e))?;
+
+    if response.status().is_success() {
+        response.json().await.map_err(|e| format!("Fehler beim Parsen: {}", e))
+    } else {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        Err(format!("GitHub API Fehler ({}): {}", status, error_text))
+    }
+}
+
+/// Lowercases, strips punctuation, and drops stopwords so titles that only
+/// differ in casing, trailing punctuation, or shared function words don't
+/// look more alike than they really are.
+fn normalize_title(title: &str) -> HashSet<String> {
+    title
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .filter(|word| !TITLE_STOPWORDS.contains(word))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 1.0 for an exact match on meaningful words, otherwise the Jaccard
+/// overlap of the two titles' word sets once stopwords are removed.
+fn title_similarity(a: &str, b: &str) -> f64 {
+    let words_a = normalize_title(a);
+    let words_b = normalize_title(b);
+    if words_a.is_empty() || words_b.is_empty() {
+        return 0.0;
+    }
+    if words_a == words_b {
+        return 1.0;
+    }
+
+    let overlap = words_a.intersection(&words_b).count() as f64;
+    let union = words_a.union(&words_b).count() as f64;
+    overlap / union
+}
+
+/// Searches open issues in the tracker for one whose title is a close
+/// match for `title`, returning the best match at or above `threshold`.
+/// Best-effort: a search failure just means we skip dedup, not that
+/// filing the report should fail.
+async fn find_similar_issue(client: &reqwest::Client, title: &str, threshold: f64) -> Option<GitHubIssueResponse> {
+    let query = format!("repo:{} in:title state:open {}", GITHUB_REPO, title);
+    let response = client
+        .get("https://api.github.com/search/issues")
+        .query(&[("q", query.as_str())])
+        .timeout(GITHUB_REQUEST_TIMEOUT)
+        .header("Authorization", format!("Bearer {}", GITHUB_TOKEN))
+        .header("User-Agent", "Tally-App")
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28")
+        .send()
+        .await
+        .ok()?;
+
+    if !response.status().is_success() {
+        return None;
+    }
+
+    let results: SearchIssuesResponse = response.json().await.ok()?;
+    results
+        .items
+        .into_iter()
+        .map(|item| (title_similarity(title, &item.title), item))
+        .filter(|(score, _)| *score >= threshold)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, item)| GitHubIssueResponse {
+            number: item.number,
+            html_url: item.html_url,
+        })
+}
+
+async fn post_comment(client: &reqwest::Client, issue_number: u64, body: &str) -> Result<String, String> {
+    let response = client
+        .post(format!(
+            "https://api.github.com/repos/{}/issues/{}/comments",
+            GITHUB_REPO, issue_number
+        ))
+        .timeout(GITHUB_REQUEST_TIMEOUT)
+        .header("Authorization", format!("Bearer {}", GITHUB_TOKEN))
+        .header("User-Agent", "Tally-App")
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28")
+        .json(&serde_json::json!({ "body": body }))
+        .send()
+        .await
+        .map_err(|e| format!("Netzwerkfehler: {}", e))?;
+
+    if response.status().is_success() {
+        let comment: serde_json::Value = response.json().await.map_err(|e| format!("Fehler beim Parsen: {}", e))?;
+        Ok(comment.get("html_url").and_then(|v| v.as_str()).unwrap_or_default().to_string())
+    } else {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        Err(format!("GitHub API Fehler ({}): {}", status, error_text))
+    }
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_feedback(
+    client: tauri::State<'_, HttpClient>,
+    app: AppHandle,
+    feedback_type: String,
+    title: String,
+    description: String,
+    app_version: String,
+    dedupe_threshold: Option<f64>,
+    confirm_duplicate_issue: Option<u64>,
+    force: Option<bool>,
+) -> Result<FeedbackResult, String> {
+    let title = title.trim().to_string();
+    let description = description.trim().to_string();
+
+    if title.is_empty() {
+        return Ok(FeedbackResult {
+            success: false,
+            issue_number: None,
+            issue_url: None,
+            queued: false,
+            deduplicated: false,
+            needs_confirmation: false,
+            error: Some("Titel darf nicht leer sein".to_string()),
+        });
+    }
+
+    let body = format!(
+        "{}\n\n---\n*Gesendet aus Tally v{} via In-App Feedback*",
+        description, app_version
+    );
+
+    // The frontend already confirmed this looks like `confirm_duplicate_issue`
+    // — add a comment there instead of filing a fresh report.
+    if let Some(issue_number) = confirm_duplicate_issue {
+        return match post_comment(&client.0, issue_number, &body).await {
+            Ok(comment_url) => Ok(FeedbackResult {
+                success: true,
+                issue_number: Some(issue_number),
+                issue_url: Some(comment_url),
+                queued: false,
+                deduplicated: true,
+                needs_confirmation: false,
+                error: None,
+            }),
+            Err(error) => Ok(FeedbackResult {
+                success: false,
+                issue_number: None,
+                issue_url: None,
+                queued: false,
+                deduplicated: false,
+                needs_confirmation: false,
+                error: Some(error),
+            }),
+        };
+    }
+
+    // With `force`, the user declined a previously suggested match — go
+    // straight to filing, skipping the search.
+    let skip_dedup = force.unwrap_or(false);
+    if !skip_dedup {
+        let threshold = dedupe_threshold.unwrap_or(DEFAULT_DEDUPE_THRESHOLD);
+        if let Some(existing) = find_similar_issue(&client.0, &title, threshold).await {
+            return Ok(FeedbackResult {
+                success: false,
+                issue_number: Some(existing.number),
+                issue_url: Some(existing.html_url),
+                queued: false,
+                deduplicated: false,
+                needs_confirmation: true,
+                error: None,
+            });
+        }
+    }
+
+    let issue = GitHubIssueRequest {
+        title,
+        body,
+        labels: labels_for(&feedback_type),
+    };
+
+    match post_issue(&client.0, &issue).await {
+        Ok(response) => Ok(FeedbackResult {
+            success: true,
+            issue_number: Some(response.number),
+            issue_url: Some(response.html_url),
+            queued: false,
+            deduplicated: false,
+            needs_confirmation: false,
+            error: None,
+        }),
+        Err(error) if error.starts_with("Netzwerkfehler") => {
+            let mut queue = read_queue(&app);
+            queue.push(QueuedFeedback {
+                issue,
+                attempts: 0,
+                next_retry_at: now_secs() + backoff_secs(0),
+            });
+            write_queue(&app, &queue);
+
+            Ok(FeedbackResult {
+                success: false,
+                issue_number: None,
+                issue_url: None,
+                queued: true,
+                deduplicated: false,
+                needs_confirmation: false,
+                error: Some(error),
+            })
+        }
+        Err(error) => Ok(FeedbackResult {
+            success: false,
+            issue_number: None,
+            issue_url: None,
+            queued: false,
+            deduplicated: false,
+            needs_confirmation: false,
+            error: Some(error),
+        }),
+    }
+}
+
+/// Retries every due item in the offline outbox. Items that succeed are
+/// removed and their issue URL is broadcast via a `feedback-queue-flushed`
+/// event; items that fail again have their attempt count and backoff
+/// bumped and stay queued.
+pub async fn flush_queue(client: &reqwest::Client, app: &AppHandle) {
+    let mut queue = read_queue(app);
+    if queue.is_empty() {
+        return;
+    }
+
+    let now = now_secs();
+    let mut remaining = Vec::with_capacity(queue.len());
+
+    for mut item in queue.drain(..) {
+        if item.next_retry_at > now {
+            remaining.push(item);
+            continue;
+        }
+
+        match post_issue(client, &item.issue).await {
+            Ok(response) => {
+                let _ = app.emit("feedback-queue-flushed", &response.html_url);
+            }
+            Err(_) => {
+                item.attempts += 1;
+                item.next_retry_at = now + backoff_secs(item.attempts);
+                remaining.push(item);
+            }
+        }
+    }
+
+    write_queue(app, &remaining);
+}
+
+#[tauri::command]
+pub async fn flush_feedback_queue(client: tauri::State<'_, HttpClient>, app: AppHandle) -> Result<(), String> {
+    flush_queue(&client.0, &app).await;
+    Ok(())
+}
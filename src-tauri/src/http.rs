@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+/// The single pooled `reqwest::Client` shared by every command, managed as
+/// Tauri state so `submit_feedback` and the OG fetchers reuse the same
+/// connection pool and TLS session cache instead of paying a fresh
+/// handshake on every invoke.
+pub struct HttpClient(pub reqwest::Client);
+
+pub fn build() -> reqwest::Client {
+    reqwest::Client::builder()
+        .gzip(true)
+        .brotli(true)
+        .deflate(true)
+        .pool_idle_timeout(Duration::from_secs(90))
+        .redirect(reqwest::redirect::Policy::limited(5))
+        .build()
+        .expect("failed to build shared HTTP client")
+}
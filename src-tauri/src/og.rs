@@ -0,0 +1,235 @@
+use std::collections::{BTreeMap, HashMap};
+
+use html5gum::{Token, Tokenizer};
+use serde::Serialize;
+
+use crate::favicon;
+
+#[derive(Debug, Serialize)]
+pub struct OgMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub site_name: Option<String>,
+    pub favicon: Option<String>,
+    pub url: String,
+}
+
+/// Everything we pull out of `<head>` while tokenizing: the lowercased
+/// `meta` name/property → content map, the page `<title>`, and any
+/// `<link>` tags worth looking at afterwards (favicons among them).
+struct HeadTags {
+    meta: HashMap<String, String>,
+    title: Option<String>,
+    links: Vec<HashMap<String, String>>,
+}
+
+/// Hard backstop on how much of the document we'll ever tokenize. `</head>`
+/// is the normal stop condition, but it's legal HTML to omit it entirely,
+/// so a malformed or adversarial page must not be walked to EOF.
+const MAX_HEAD_SCAN_BYTES: usize = 256 * 1024;
+
+/// Drives the tokenizer over `html` and stops as soon as `</head>` closes
+/// (or `MAX_HEAD_SCAN_BYTES` is exhausted), so we never have to guess a
+/// byte cutoff as tight as the old 20KB window while still bounding work
+/// on a page with no `</head>` at all.
+fn collect_head_tags(html: &str) -> HeadTags {
+    let mut bound = MAX_HEAD_SCAN_BYTES.min(html.len());
+    while bound > 0 && !html.is_char_boundary(bound) {
+        bound -= 1;
+    }
+    let html = &html[..bound];
+
+    let mut meta = HashMap::new();
+    let mut links = Vec::new();
+    let mut title: Option<String> = None;
+    let mut title_buf = String::new();
+    let mut in_title = false;
+
+    // `Tokenizer` yields `Result<Token, Infallible>` — the HTML5 spec has no
+    // unrecoverable parse errors, so `unwrap()` here can never panic.
+    for token in Tokenizer::new(html).map(|r| r.unwrap()) {
+        match token {
+            Token::StartTag(tag) => {
+                let name = tag.name.to_ascii_lowercase();
+                match name.as_slice() {
+                    b"meta" => {
+                        let attrs = lower_attrs(&tag.attributes);
+                        let key = attrs.get("property").or_else(|| attrs.get("name"));
+                        if let (Some(key), Some(content)) = (key, attrs.get("content")) {
+                            meta.entry(key.to_ascii_lowercase()).or_insert_with(|| content.clone());
+                        }
+                    }
+                    b"link" => links.push(lower_attrs(&tag.attributes)),
+                    b"title" => in_title = true,
+                    _ => {}
+                }
+            }
+            // A title's character data can arrive as several String tokens
+            // (e.g. split around a decoded entity), so accumulate all of
+            // them rather than keeping only the first.
+            Token::String(text) if in_title => {
+                title_buf.push_str(&String::from_utf8_lossy(&text));
+            }
+            Token::EndTag(tag) => {
+                let name = tag.name.to_ascii_lowercase();
+                if name == b"title" {
+                    in_title = false;
+                    let trimmed = title_buf.trim();
+                    if title.is_none() && !trimmed.is_empty() {
+                        title = Some(trimmed.to_string());
+                    }
+                } else if name == b"head" {
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if title.is_none() {
+        let trimmed = title_buf.trim();
+        if !trimmed.is_empty() {
+            title = Some(trimmed.to_string());
+        }
+    }
+
+    HeadTags { meta, title, links }
+}
+
+/// Tokenizer attribute keys/values are case-sensitive `HtmlString`s stored
+/// in a `BTreeMap` (per `html5gum::Tag`); fold them into a plain
+/// lowercased-key `String` map so callers don't have to care about casing
+/// or attribute order.
+fn lower_attrs(attrs: &BTreeMap<html5gum::HtmlString, html5gum::HtmlString>) -> HashMap<String, String> {
+    attrs
+        .iter()
+        .map(|(k, v)| {
+            (
+                String::from_utf8_lossy(k).to_ascii_lowercase(),
+                String::from_utf8_lossy(v).to_string(),
+            )
+        })
+        .collect()
+}
+
+fn resolve_url(value: &str, base_url: &str) -> String {
+    if value.starts_with("http://") || value.starts_with("https://") {
+        value.to_string()
+    } else if let Some(rest) = value.strip_prefix("//") {
+        format!("https://{}", rest)
+    } else {
+        let base = base_url.trim_end_matches('/');
+        let path = value.trim_start_matches('/');
+        format!("{}/{}", base, path)
+    }
+}
+
+fn first_og_or_twitter(meta: &HashMap<String, String>, og_key: &str, twitter_key: &str) -> Option<String> {
+    meta.get(og_key).or_else(|| meta.get(twitter_key)).cloned()
+}
+
+fn get_base_url(url: &str) -> String {
+    if let Some(scheme_end) = url.find("://") {
+        if let Some(path_start) = url[scheme_end + 3..].find('/') {
+            return url[..scheme_end + 3 + path_start].to_string();
+        }
+    }
+    url.to_string()
+}
+
+fn get_host(base_url: &str) -> &str {
+    base_url
+        .split("://")
+        .nth(1)
+        .unwrap_or(base_url)
+        .trim_end_matches('/')
+}
+
+async fn fetch_one(client: &reqwest::Client, app: &tauri::AppHandle, url: String, favicon_ttl: std::time::Duration) -> Result<OgMetadata, String> {
+    let response = client
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(5))
+        .header("User-Agent", "Mozilla/5.0 (compatible; TallyApp/1.0)")
+        .header("Accept", "text/html")
+        .send()
+        .await
+        .map_err(|e| format!("Netzwerkfehler: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+
+    let html = response
+        .text()
+        .await
+        .map_err(|e| format!("Fehler beim Lesen: {}", e))?;
+
+    let base_url = get_base_url(&url);
+    let head = collect_head_tags(&html);
+
+    let image = first_og_or_twitter(&head.meta, "og:image", "twitter:image").map(|img| resolve_url(&img, &base_url));
+
+    let host = get_host(&base_url).to_string();
+    let favicon = favicon::resolve(
+        client,
+        app,
+        &host,
+        &head.links,
+        |href| resolve_url(href, &base_url),
+        &base_url,
+        favicon_ttl,
+    )
+    .await;
+
+    Ok(OgMetadata {
+        title: first_og_or_twitter(&head.meta, "og:title", "twitter:title").or(head.title),
+        description: first_og_or_twitter(&head.meta, "og:description", "twitter:description"),
+        image,
+        site_name: head.meta.get("og:site_name").cloned(),
+        favicon,
+        url,
+    })
+}
+
+#[tauri::command]
+pub async fn fetch_og_metadata(
+    client: tauri::State<'_, crate::HttpClient>,
+    app: tauri::AppHandle,
+    url: String,
+    favicon_ttl_secs: Option<u64>,
+) -> Result<OgMetadata, String> {
+    let favicon_ttl = favicon_ttl_secs.map(std::time::Duration::from_secs).unwrap_or(favicon::DEFAULT_TTL);
+    fetch_one(&client.0, &app, url, favicon_ttl).await
+}
+
+/// Maximum number of in-flight requests allowed when resolving a batch of
+/// URLs, so a large tally page doesn't open dozens of sockets to the same
+/// handful of hosts at once.
+const BATCH_CONCURRENCY: usize = 8;
+
+/// Resolves OG metadata for a whole list of URLs in one invoke, preserving
+/// input order. Concurrency is capped by a semaphore so one batch can't
+/// exhaust the connection pool or hammer a single host.
+#[tauri::command]
+pub async fn fetch_og_metadata_batch(
+    client: tauri::State<'_, crate::HttpClient>,
+    app: tauri::AppHandle,
+    urls: Vec<String>,
+    favicon_ttl_secs: Option<u64>,
+) -> Result<Vec<Result<OgMetadata, String>>, String> {
+    let favicon_ttl = favicon_ttl_secs.map(std::time::Duration::from_secs).unwrap_or(favicon::DEFAULT_TTL);
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(BATCH_CONCURRENCY));
+
+    let fetches = urls.into_iter().map(|url| {
+        let client = client.0.clone();
+        let app = app.clone();
+        let semaphore = semaphore.clone();
+        async move {
+            let _permit = semaphore.acquire().await.expect("semaphore closed");
+            fetch_one(&client, &app, url, favicon_ttl).await
+        }
+    });
+
+    Ok(futures::future::join_all(fetches).await)
+}